@@ -1,15 +1,19 @@
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
-    event::{poll, read, Event, KeyCode, KeyModifiers},
+    event::{
+        poll, read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers,
+        MouseButton, MouseEventKind,
+    },
     execute, queue,
-    style::Print,
+    style::{Color, Print, ResetColor, SetForegroundColor},
     terminal::{
         disable_raw_mode, enable_raw_mode, size, Clear, ClearType, EnterAlternateScreen,
         LeaveAlternateScreen,
     },
 };
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
+    env, fs,
     io::{self, stdout, Write},
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -23,33 +27,78 @@ fn main() -> io::Result<()> {
     let stop_signal = Arc::new(AtomicBool::new(false));
     signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&stop_signal))?;
 
+    let args: Vec<String> = env::args().collect();
+    let load_path = parse_arg(&args, "--load");
+
     // Initialize game
     let (width, height) = size().unwrap();
     let mut game = Game::new(width, height);
-    game.seed();
+    if let Some(rulestring) = parse_arg(&args, "--rule") {
+        game.rule =
+            Rule::parse(&rulestring).unwrap_or_else(|err| panic!("invalid --rule: {}", err));
+    }
+    if let Some(tps) = parse_arg(&args, "--tps") {
+        let parsed: u32 = tps
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid --tps: {}", tps));
+        game.tps = parsed.max(1);
+    }
+    if args.iter().any(|arg| arg == "--wrap") {
+        game.board_shape.boundary = Boundary::Toroidal;
+    }
+    match load_path {
+        Some(path) => {
+            let contents = fs::read_to_string(&path)?;
+            let pattern = Pattern::parse(&contents)
+                .unwrap_or_else(|err| panic!("failed to parse pattern {}: {}", path, err));
+            game.load_pattern(&pattern, None);
+        }
+        None => game.seed(),
+    }
 
     // Enter alternate screen terminal buffer
-    execute!(stdout(), EnterAlternateScreen, Hide)?;
+    execute!(stdout(), EnterAlternateScreen, Hide, EnableMouseCapture)?;
     enable_raw_mode()?;
 
     let mut paused = false;
     // While no stop signal was received, keep iterating
     while !stop_signal.load(Ordering::Relaxed) {
-        if poll(time::Duration::from_millis(200))? {
+        if poll(game.tick_duration())? {
             match read()? {
-                Event::Key(event) => match event.code {
-                    KeyCode::Char(char) => match char {
-                        'q' => break,
-                        'c' if event.modifiers.contains(KeyModifiers::CONTROL) => break,
-                        ' ' | 'p' => paused = !paused,
-                        'n' if paused => {
-                            game.display()?;
-                            game.next();
+                Event::Key(event) => {
+                    if let KeyCode::Char(char) = event.code {
+                        match char {
+                            'q' => break,
+                            'c' if event.modifiers.contains(KeyModifiers::CONTROL) => break,
+                            ' ' | 'p' => paused = !paused,
+                            'n' if paused => {
+                                game.display()?;
+                                game.next();
+                            }
+                            's' => fs::write(SAVE_PATH, game.dump_pattern().serialize())?,
+                            '+' => game.tps = game.tps.saturating_add(1),
+                            '-' => game.tps = game.tps.saturating_sub(1).max(1),
+                            _ => (),
+                        }
+                    }
+                }
+                Event::Mouse(event) => {
+                    let cell = (event.column, event.row);
+                    match event.kind {
+                        MouseEventKind::Down(MouseButton::Left)
+                        | MouseEventKind::Drag(MouseButton::Left) => {
+                            game.cells.entry(cell).or_insert(0);
+                        }
+                        MouseEventKind::Down(MouseButton::Right)
+                        | MouseEventKind::Drag(MouseButton::Right) => {
+                            game.cells.remove(&cell);
                         }
                         _ => (),
-                    },
-                    _ => (),
-                },
+                    }
+                    if paused {
+                        game.display()?;
+                    }
+                }
                 Event::Resize(width, height) => game.resize_board(width, height),
                 _ => (),
             }
@@ -61,74 +110,324 @@ fn main() -> io::Result<()> {
 
     // Reset terminal screen
     disable_raw_mode()?;
-    execute!(io::stdout(), LeaveAlternateScreen, Show)
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        Show,
+        DisableMouseCapture
+    )
+}
+
+const SAVE_PATH: &str = "life.rle";
+
+/// Looks up a `--flag value` pair in the process arguments.
+fn parse_arg(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
 }
 
 type Cell = (u16, u16);
 
+const DEFAULT_TPS: u32 = 5;
+
 #[derive(Debug)]
 struct Game {
-    cells: HashSet<Cell>,
+    cells: HashMap<Cell, u32>,
     board_shape: BoardShape,
     generation: u32,
+    rule: Rule,
+    tps: u32,
 }
 
 #[derive(Debug)]
 struct BoardShape {
     width: u16,
     height: u16,
+    boundary: Boundary,
+}
+
+/// How the board handles coordinates past its edges.
+#[derive(Debug)]
+enum Boundary {
+    /// Neighbors past the edge are clipped, so patterns die at the border.
+    Bounded,
+    /// Neighbors past the edge wrap around to the opposite side.
+    Toroidal,
+}
+
+/// A Life-like rulestring in B/S notation (e.g. `B3/S23` for Conway's Life),
+/// expanded into lookup tables indexed by live-neighbor count.
+#[derive(Debug)]
+struct Rule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl Rule {
+    const CONWAY: &'static str = "B3/S23";
+
+    /// Parses a `B<digits>/S<digits>` rulestring.
+    fn parse(rulestring: &str) -> Result<Rule, String> {
+        let mut birth = [false; 9];
+        let mut survival = [false; 9];
+
+        for part in rulestring.split('/') {
+            if part.is_empty() {
+                continue;
+            }
+            let (prefix, digits) = part.split_at(1);
+            let table = match prefix {
+                "B" | "b" => &mut birth,
+                "S" | "s" => &mut survival,
+                _ => return Err(format!("unknown rule section '{}'", part)),
+            };
+            for digit in digits.chars() {
+                let count = digit
+                    .to_digit(10)
+                    .filter(|count| *count <= 8)
+                    .ok_or_else(|| format!("invalid neighbor count '{}'", digit))?
+                    as usize;
+                table[count] = true;
+            }
+        }
+
+        Ok(Rule { birth, survival })
+    }
+
+    /// Renders the rule back into `B<digits>/S<digits>` notation.
+    fn to_rulestring(&self) -> String {
+        let digits = |table: &[bool; 9]| -> String {
+            (0..9)
+                .filter(|count| table[*count])
+                .map(|count| count.to_string())
+                .collect()
+        };
+
+        format!("B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        Rule::parse(Rule::CONWAY).unwrap()
+    }
+}
+
+/// Maps a cell's age in generations to a color, from bright for newly born
+/// cells to cooler, dimmer tones for long-lived ones.
+fn age_color(age: u32) -> Color {
+    match age {
+        0..=2 => Color::White,
+        3..=7 => Color::Yellow,
+        8..=15 => Color::Cyan,
+        _ => Color::Blue,
+    }
+}
+
+/// A pattern loaded from or dumped to disk, as a set of live cells relative
+/// to its own top-left origin.
+#[derive(Debug)]
+struct Pattern {
+    cells: HashSet<Cell>,
+    width: u16,
+    height: u16,
+    rulestring: String,
+}
+
+impl Pattern {
+    /// Parses either the plaintext (`.`/`O` grid) or RLE format, detected by
+    /// whether the first non-comment line starts with a `x = ` header.
+    fn parse(contents: &str) -> Result<Pattern, String> {
+        let body: Vec<&str> = contents
+            .lines()
+            .filter(|line| !line.starts_with('!') && !line.starts_with('#'))
+            .collect();
+
+        match body.first() {
+            Some(line) if line.trim_start().starts_with("x ") => Pattern::parse_rle(&body),
+            _ => Pattern::parse_plaintext(&body),
+        }
+    }
+
+    fn parse_plaintext(lines: &[&str]) -> Result<Pattern, String> {
+        let mut cells = HashSet::new();
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0) as u16;
+        let height = lines.len() as u16;
+
+        for (row, line) in lines.iter().enumerate() {
+            for (col, char) in line.chars().enumerate() {
+                if char == 'O' {
+                    cells.insert((col as u16, row as u16));
+                }
+            }
+        }
+
+        Ok(Pattern {
+            cells,
+            width,
+            height,
+            rulestring: Rule::CONWAY.to_string(),
+        })
+    }
+
+    fn parse_rle(lines: &[&str]) -> Result<Pattern, String> {
+        let header = lines.first().ok_or("missing RLE header")?;
+        let mut width = 0u16;
+        let mut height = 0u16;
+        for field in header.split(',') {
+            let mut parts = field.split('=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().unwrap_or("").trim();
+            match key {
+                "x" => width = value.parse().map_err(|_| "invalid x in RLE header")?,
+                "y" => height = value.parse().map_err(|_| "invalid y in RLE header")?,
+                _ => (),
+            }
+        }
+
+        let mut cells = HashSet::new();
+        let (mut col, mut row) = (0u16, 0u16);
+        let mut run_length = String::new();
+
+        'rows: for char in lines[1..].join("").chars() {
+            if char.is_ascii_digit() {
+                run_length.push(char);
+                continue;
+            }
+
+            let count = run_length.parse::<u16>().unwrap_or(1);
+            run_length.clear();
+
+            match char {
+                'b' => col += count,
+                'o' => {
+                    for _ in 0..count {
+                        cells.insert((col, row));
+                        col += 1;
+                    }
+                }
+                '$' => {
+                    row += count;
+                    col = 0;
+                }
+                '!' => break 'rows,
+                _ => (),
+            }
+        }
+
+        Ok(Pattern {
+            cells,
+            width,
+            height,
+            rulestring: Rule::CONWAY.to_string(),
+        })
+    }
+
+    /// Builds a `Pattern` sized to the bounding box of `cells`, with an offset
+    /// applied so coordinates start at the pattern's own origin.
+    fn from_cells(cells: &HashSet<Cell>, rulestring: &str) -> Pattern {
+        let min_x = cells.iter().map(|(x, _)| *x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|(_, y)| *y).min().unwrap_or(0);
+        let max_x = cells.iter().map(|(x, _)| *x).max().unwrap_or(0);
+        let max_y = cells.iter().map(|(_, y)| *y).max().unwrap_or(0);
+
+        let shifted = cells.iter().map(|(x, y)| (x - min_x, y - min_y)).collect();
+
+        Pattern {
+            cells: shifted,
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1,
+            rulestring: rulestring.to_string(),
+        }
+    }
+
+    fn serialize(&self) -> String {
+        let mut output = format!(
+            "x = {}, y = {}, rule = {}\n",
+            self.width, self.height, self.rulestring
+        );
+        let mut line = String::new();
+
+        for row in 0..self.height {
+            let mut col = 0;
+            while col < self.width {
+                let alive = self.cells.contains(&(col, row));
+                let run_start = col;
+                while col < self.width && self.cells.contains(&(col, row)) == alive {
+                    col += 1;
+                }
+                let run_length = col - run_start;
+                if run_length > 1 {
+                    line.push_str(&run_length.to_string());
+                }
+                line.push(if alive { 'o' } else { 'b' });
+            }
+            line.push('$');
+            output.push_str(&line);
+            output.push('\n');
+            line.clear();
+        }
+
+        output.push('!');
+        output
+    }
 }
 
 impl Game {
     fn new(width: u16, height: u16) -> Game {
         Game {
-            cells: HashSet::new(),
+            cells: HashMap::new(),
             board_shape: BoardShape {
                 width,
                 height: height - 1,
+                boundary: Boundary::Bounded,
             },
             generation: 0,
+            rule: Rule::default(),
+            tps: DEFAULT_TPS,
         }
     }
 
+    /// The poll duration for one simulation tick at the current `tps`.
+    fn tick_duration(&self) -> time::Duration {
+        time::Duration::from_millis(1000 / self.tps as u64)
+    }
+
     fn seed(&mut self) {
-        let BoardShape { width, height } = self.board_shape;
+        let BoardShape { width, height, .. } = self.board_shape;
         for i in 0..width {
             for j in 0..height {
                 // A 50% chance of populating the cell
                 if rand::random::<f32>() < 0.2 {
-                    self.cells.insert((i, j));
+                    self.cells.insert((i, j), 0);
                 }
             }
         }
     }
 
     fn next(&mut self) {
-        let mut next_generation = HashSet::new();
-        for cell in self.cells.iter() {
-            let neighbors = self.cell_neighbors(cell);
-            // Check if the current cell should live on to the next generation
-            let alive_neighbors = neighbors
-                .iter()
-                .filter(|cell| self.cells.contains(cell))
-                .count();
-            if let 2 | 3 = alive_neighbors {
-                next_generation.insert(*cell);
+        // Accumulate each cell's live-neighbor count in a single pass over
+        // the live cells, instead of re-scanning every dead neighbor's own
+        // neighborhood against the whole set.
+        let mut neighbor_counts: HashMap<Cell, u8> = HashMap::new();
+        for cell in self.cells.keys() {
+            for neighbor in self.cell_neighbors(cell) {
+                *neighbor_counts.entry(neighbor).or_insert(0) += 1;
             }
+        }
 
-            // Check if any of its dead neighbors should become alive
-            let dead_neighbors = neighbors.iter().filter(|cell| !self.cells.contains(cell));
-            for cell in dead_neighbors {
-                let alive_neighbors = self.cell_neighbors(cell).iter().fold(0, |acc, cell| {
-                    if self.cells.contains(cell) {
-                        acc + 1
-                    } else {
-                        acc
-                    }
-                });
-                if alive_neighbors == 3 {
-                    next_generation.insert(*cell);
+        let mut next_generation = HashMap::new();
+        for (cell, count) in neighbor_counts {
+            match self.cells.get(&cell) {
+                Some(age) if self.rule.survival[count as usize] => {
+                    next_generation.insert(cell, age + 1);
+                }
+                None if self.rule.birth[count as usize] => {
+                    next_generation.insert(cell, 0);
                 }
+                _ => (),
             }
         }
 
@@ -140,27 +439,55 @@ impl Game {
         let mut stdout = stdout();
         for row in 0..self.board_shape.width {
             for col in 0..self.board_shape.height {
-                let cell = self.cells.get(&(row, col));
-                let char = match cell {
-                    Some(_) => "█",
-                    None => " ",
-                };
-                queue!(stdout, MoveTo(row, col), Print(char))?;
+                match self.cells.get(&(row, col)) {
+                    Some(age) => queue!(
+                        stdout,
+                        MoveTo(row, col),
+                        SetForegroundColor(age_color(*age)),
+                        Print("█")
+                    )?,
+                    None => queue!(stdout, MoveTo(row, col), Print(" "))?,
+                }
             }
         }
         queue!(
             stdout,
+            ResetColor,
             MoveTo(0, self.board_shape.height + 1),
             Print(format!(
-                "Generation: {}  Population: {}",
+                "Generation: {}  Population: {}  TPS: {}",
                 self.generation,
-                self.cells.len()
+                self.cells.len(),
+                self.tps
             )),
             Clear(ClearType::UntilNewLine),
         )?;
         stdout.flush()
     }
 
+    /// Seeds `cells` from a `Pattern`, centering it on the board unless an
+    /// explicit `(col, row)` offset is given.
+    fn load_pattern(&mut self, pattern: &Pattern, offset: Option<Cell>) {
+        let (offset_x, offset_y) = offset.unwrap_or((
+            (self.board_shape.width / 2).saturating_sub(pattern.width / 2),
+            (self.board_shape.height / 2).saturating_sub(pattern.height / 2),
+        ));
+
+        self.cells = pattern
+            .cells
+            .iter()
+            .map(|(x, y)| ((x + offset_x, y + offset_y), 0))
+            .collect();
+    }
+
+    /// Dumps the current live cells as a `Pattern` sized to their bounding box.
+    fn dump_pattern(&self) -> Pattern {
+        Pattern::from_cells(
+            &self.cells.keys().copied().collect(),
+            &self.rule.to_rulestring(),
+        )
+    }
+
     fn resize_board(&mut self, width: u16, height: u16) {
         self.board_shape.width = width;
         self.board_shape.height = height - 1;
@@ -168,18 +495,38 @@ impl Game {
 
     fn cell_neighbors(&self, cell: &Cell) -> Vec<Cell> {
         let (i, j) = cell;
-        let mut neighbors = Vec::new();
-        let row_range = if *i > 0 { i - 1..=i + 1 } else { *i..=i + 1 };
-        let col_range = if *j > 0 { j - 1..=j + 1 } else { *j..=j + 1 };
-
-        for i in row_range {
-            for j in col_range.clone() {
-                if i < self.board_shape.width && j < self.board_shape.height && (i, j) != *cell {
-                    neighbors.push((i, j))
+        let BoardShape {
+            width,
+            height,
+            ref boundary,
+        } = self.board_shape;
+
+        match boundary {
+            Boundary::Bounded => {
+                let mut neighbors = Vec::new();
+                let row_range = if *i > 0 { i - 1..=i + 1 } else { *i..=i + 1 };
+                let col_range = if *j > 0 { j - 1..=j + 1 } else { *j..=j + 1 };
+
+                for i in row_range {
+                    for j in col_range.clone() {
+                        if i < width && j < height && (i, j) != *cell {
+                            neighbors.push((i, j))
+                        }
+                    }
                 }
+
+                neighbors
             }
+            Boundary::Toroidal => (-1..=1)
+                .flat_map(|row_offset| (-1..=1).map(move |col_offset| (row_offset, col_offset)))
+                .filter(|offset| *offset != (0, 0))
+                .map(|(row_offset, col_offset)| {
+                    (
+                        (*i as i32 + row_offset).rem_euclid(width as i32) as u16,
+                        (*j as i32 + col_offset).rem_euclid(height as i32) as u16,
+                    )
+                })
+                .collect(),
         }
-
-        return neighbors;
     }
 }